@@ -7,6 +7,8 @@ use std::fmt::Debug;
 
 use webpath::WebPath;
 use hyper::status::StatusCode;
+use futures::{Future, Stream};
+use base64;
 
 macro_rules! notimplemented {
     ($method:expr) => {
@@ -42,6 +44,49 @@ pub struct DavProp {
     pub xml:        Option<Vec<u8>>,
 }
 
+/// Namespace under which `xattr_to_davprop`/`davprop_to_xattr_name` expose
+/// filesystem extended attributes as DAV properties.
+pub const XATTR_NAMESPACE: &'static str = "http://webdav-handler-rs/xattr/";
+
+/// Map one extended attribute (name, value) pair onto a `DavProp` in
+/// [`XATTR_NAMESPACE`].
+///
+/// An xattr value is arbitrary binary data, but `DavProp.xml` ends up
+/// serialized as XML element content, so the raw bytes can't be dropped in
+/// as-is -- they may not even be valid UTF-8, let alone free of bytes that
+/// are illegal in XML text. The value is therefore base64-encoded; pair
+/// this with [`xattr_value_from_davprop`] (and [`davprop_to_xattr_name`]
+/// for the name) to get a lossless, generic PROPFIND/PROPPATCH round-trip
+/// without hand-rolling the translation, or the escaping, in every backend.
+pub fn xattr_to_davprop(name: &str, value: &[u8]) -> DavProp {
+    DavProp{
+        name:       name.to_string(),
+        prefix:     None,
+        namespace:  Some(XATTR_NAMESPACE.to_string()),
+        xml:        Some(base64::encode(value).into_bytes()),
+    }
+}
+
+/// Recover the xattr name from a `DavProp`, if it is one of ours
+/// (i.e. its namespace is [`XATTR_NAMESPACE`]).
+pub fn davprop_to_xattr_name(prop: &DavProp) -> Option<&str> {
+    match prop.namespace {
+        Some(ref ns) if ns == XATTR_NAMESPACE => Some(prop.name.as_str()),
+        _ => None,
+    }
+}
+
+/// Recover the raw xattr value from a `DavProp` built by [`xattr_to_davprop`].
+///
+/// Returns `FsError::GeneralFailure` if the property's content isn't
+/// valid base64 (e.g. it didn't actually come from `xattr_to_davprop`).
+pub fn xattr_value_from_davprop(prop: &DavProp) -> FsResult<Vec<u8>> {
+    match prop.xml {
+        Some(ref xml) => base64::decode(xml).map_err(|_| FsError::GeneralFailure),
+        None => Ok(Vec::new()),
+    }
+}
+
 /// The trait that defines a filesystem.
 ///
 /// The BoxCloneFs trait is a helper trait that is automatically implemented
@@ -67,6 +112,22 @@ pub trait DavFileSystem : Debug + Sync + Send + BoxCloneFs {
         self.metadata(path)
     }
 
+    /// Read the target of a symbolic link.
+    ///
+    /// Has a default "notimplemented" implementation.
+    #[allow(unused_variables)]
+    fn read_link(&self, path: &WebPath) -> FsResult<WebPath> {
+        notimplemented!("read_link")
+    }
+
+    /// Create a symbolic link at `path` pointing to `target`.
+    ///
+    /// Has a default "notimplemented" implementation.
+    #[allow(unused_variables)]
+    fn create_symlink(&self, path: &WebPath, target: &WebPath) -> FsResult<()> {
+        notimplemented!("create_symlink")
+    }
+
     /// Create a directory.
     ///
     /// Has a default "notimplemented" implementation.
@@ -104,6 +165,24 @@ pub trait DavFileSystem : Debug + Sync + Send + BoxCloneFs {
         notimplemented!("rename")
     }
 
+    /// Rename a file or directory, honoring explicit `RenameOptions`.
+    ///
+    /// Added so the MOVE handler can express the `Overwrite` header
+    /// explicitly instead of relying on `rename()`'s hard-coded "replace a
+    /// file, error on a directory" semantics; should return
+    /// `FsError::Exists` when `options.overwrite` is false and the
+    /// destination exists.
+    ///
+    /// Has a default implementation that just defers to `rename()`,
+    /// ignoring `options` -- that preserves `rename()`'s own hard-coded
+    /// overwrite behaviour rather than actually honoring `options`, so a
+    /// backend that needs to honor `Overwrite: F` should override this
+    /// method directly instead of relying on the default.
+    #[allow(unused_variables)]
+    fn rename_with_options(&self, from: &WebPath, to: &WebPath, options: RenameOptions) -> FsResult<()> {
+        self.rename(from, to)
+    }
+
     /// Copy a file
     ///
     /// Should also copy the DAV properties, if properties
@@ -115,6 +194,24 @@ pub trait DavFileSystem : Debug + Sync + Send + BoxCloneFs {
         notimplemented!("copy")
     }
 
+    /// Copy a file or directory, honoring explicit `CopyOptions`.
+    ///
+    /// Added so the COPY handler can express the `Overwrite` and `Depth`
+    /// headers explicitly: should return `FsError::Exists` when
+    /// `options.overwrite` is false and the destination exists, and should
+    /// copy the whole tree when `options.depth_infinity` is true and
+    /// `from` is a directory (otherwise just the node itself, an empty
+    /// directory for a directory source).
+    ///
+    /// Has a default implementation that just defers to `copy()`,
+    /// ignoring `options` -- a backend that needs to honor `Overwrite: F`
+    /// or a non-infinity `Depth` should override this method directly
+    /// instead of relying on the default.
+    #[allow(unused_variables)]
+    fn copy_with_options(&self, from: &WebPath, to: &WebPath, options: CopyOptions) -> FsResult<()> {
+        self.copy(from, to)
+    }
+
     /// Set the access time of a file / directory.
     ///
     /// Default: notimplemented.
@@ -135,7 +232,11 @@ pub trait DavFileSystem : Debug + Sync + Send + BoxCloneFs {
 
     /// Indicator that tells if this filesystem driver supports DAV properties.
     ///
-    /// Has a default "false" implementation.
+    /// Has a default "false" implementation, since this is queried per
+    /// node on every PROPFIND and needs to be a cheap, static capability
+    /// check rather than a live lookup. A backend that relies on the
+    /// xattr-backed `get_props`/`patch_props` defaults below should
+    /// override this to return `true` unconditionally.
     #[allow(unused_variables)]
     fn have_props(&self, path: &WebPath) -> bool {
         false
@@ -143,18 +244,53 @@ pub trait DavFileSystem : Debug + Sync + Send + BoxCloneFs {
 
     /// Patch the DAV properties of a node (add/remove props)
     ///
-    /// Has a default "notimplemented" implementation.
-    #[allow(unused_variables)]
+    /// Default implementation: bridges to the xattr methods, via
+    /// `davprop_to_xattr_name`/`xattr_value_from_davprop`, so that
+    /// PROPPATCH transparently becomes `set_xattr`/`remove_xattr` calls. A
+    /// prop outside of `XATTR_NAMESPACE` is reported as `StatusCode::NotFound`,
+    /// since this backend has no other place to store it.
     fn patch_props(&self, path: &WebPath, set: Vec<DavProp>, remove: Vec<DavProp>) -> FsResult<Vec<(StatusCode, DavProp)>> {
-        notimplemented!("patch_props")
+        let mut results = Vec::with_capacity(set.len() + remove.len());
+        for prop in set {
+            let status = match davprop_to_xattr_name(&prop) {
+                Some(name) => {
+                    let value = xattr_value_from_davprop(&prop)?;
+                    match self.set_xattr(path, name, &value) {
+                        Ok(()) => StatusCode::Ok,
+                        Err(_) => StatusCode::InternalServerError,
+                    }
+                },
+                None => StatusCode::NotFound,
+            };
+            results.push((status, prop));
+        }
+        for prop in remove {
+            let status = match davprop_to_xattr_name(&prop) {
+                Some(name) => match self.remove_xattr(path, name) {
+                    Ok(()) => StatusCode::Ok,
+                    Err(_) => StatusCode::InternalServerError,
+                },
+                None => StatusCode::NotFound,
+            };
+            results.push((status, prop));
+        }
+        Ok(results)
     }
 
     /// List/get the DAV properties of a node.
     ///
-    /// Has a default "notimplemented" implementation.
-    #[allow(unused_variables)]
+    /// Default implementation: bridges to the xattr methods, mapping each
+    /// xattr name from `list_xattrs()` (and, if `do_content` is set, its
+    /// value from `get_xattr()`) through `xattr_to_davprop` so that
+    /// PROPFIND transparently exposes xattrs as live DAV properties.
     fn get_props(&self, path: &WebPath, do_content: bool) -> FsResult<Vec<DavProp>> {
-        notimplemented!("get_props")
+        let names = self.list_xattrs(path)?;
+        let mut props = Vec::with_capacity(names.len());
+        for name in names {
+            let value = if do_content { self.get_xattr(path, &name)? } else { Vec::new() };
+            props.push(xattr_to_davprop(&name, &value));
+        }
+        Ok(props)
     }
 
     /// Get one specific named property of a node.
@@ -176,6 +312,106 @@ pub trait DavFileSystem : Debug + Sync + Send + BoxCloneFs {
     fn get_quota(&self) -> FsResult<(u64, Option<u64>)> {
         notimplemented!("get_quota`")
     }
+
+    /// Get one extended attribute of a file or directory.
+    ///
+    /// Has a default "notimplemented" implementation.
+    #[allow(unused_variables)]
+    fn get_xattr(&self, path: &WebPath, name: &str) -> FsResult<Vec<u8>> {
+        notimplemented!("get_xattr")
+    }
+
+    /// Set (create or replace) one extended attribute of a file or directory.
+    ///
+    /// Has a default "notimplemented" implementation.
+    #[allow(unused_variables)]
+    fn set_xattr(&self, path: &WebPath, name: &str, value: &[u8]) -> FsResult<()> {
+        notimplemented!("set_xattr")
+    }
+
+    /// Remove one extended attribute of a file or directory.
+    ///
+    /// Has a default "notimplemented" implementation.
+    #[allow(unused_variables)]
+    fn remove_xattr(&self, path: &WebPath, name: &str) -> FsResult<()> {
+        notimplemented!("remove_xattr")
+    }
+
+    /// List the names of all extended attributes of a file or directory.
+    ///
+    /// Has a default "notimplemented" implementation.
+    #[allow(unused_variables)]
+    fn list_xattrs(&self, path: &WebPath) -> FsResult<Vec<String>> {
+        notimplemented!("list_xattrs")
+    }
+
+    /// Open a directory handle for `path`, for use with the `*_at` methods
+    /// below.
+    ///
+    /// Analogous to the WASI `*at` model: a backend that can cache an
+    /// fd/inode/cursor for the directory implements this (and the `*_at`
+    /// methods) to avoid re-walking the path from the root for every child,
+    /// which matters when the handler processes a PROPFIND over a deep
+    /// tree or a Depth:infinity COPY.
+    ///
+    /// Has a default implementation that just remembers `path` and
+    /// delegates the `*_at` methods back to the plain path-based methods.
+    fn open_dir(&self, path: &WebPath) -> FsResult<Box<DavDirHandle>> {
+        Ok(Box::new(SimpleDirHandle(path.clone())))
+    }
+
+    /// Return the metadata of `name`, relative to an already-open directory `handle`.
+    ///
+    /// Default implementation: reconstructs the full path and calls `metadata()`.
+    #[allow(unused_variables)]
+    fn metadata_at(&self, handle: &DavDirHandle, name: &[u8]) -> FsResult<Box<DavMetaData>> {
+        let mut path = handle.path().clone();
+        path.push_segment(name);
+        self.metadata(&path)
+    }
+
+    /// Open `name`, relative to an already-open directory `handle`.
+    ///
+    /// Default implementation: reconstructs the full path and calls `open()`.
+    #[allow(unused_variables)]
+    fn open_at(&self, handle: &DavDirHandle, name: &[u8], options: OpenOptions) -> FsResult<Box<DavFile>> {
+        let mut path = handle.path().clone();
+        path.push_segment(name);
+        self.open(&path, options)
+    }
+
+    /// Perform read_dir on an already-open directory `handle`.
+    ///
+    /// Default implementation: reconstructs the full path and calls `read_dir()`.
+    fn read_dir_at(&self, handle: &DavDirHandle) -> FsResult<Box< DavReadDir<Item=Box<DavDirEntry>> >> {
+        self.read_dir(handle.path())
+    }
+}
+
+/// An opaque, already-resolved directory handle, obtained from `open_dir()`.
+///
+/// Passed to the `metadata_at`/`open_at`/`read_dir_at` family so a backend
+/// that can cache an fd/inode/cursor for a directory doesn't have to
+/// re-resolve the full path from the root for every child it's asked about.
+pub trait DavDirHandle : Debug + Send {
+    /// The path this handle was opened for.
+    ///
+    /// Used by the default `*_at` method implementations on
+    /// `DavFileSystem` to reconstruct a full path; a backend that
+    /// overrides those methods to use a cached fd/inode instead doesn't
+    /// need to call this.
+    fn path(&self) -> &WebPath;
+}
+
+/// The default `DavDirHandle`, used when a backend doesn't implement
+/// `open_dir()` itself: just remembers the path it was opened for.
+#[derive(Debug)]
+struct SimpleDirHandle(WebPath);
+
+impl DavDirHandle for SimpleDirHandle {
+    fn path(&self) -> &WebPath {
+        &self.0
+    }
 }
 
 // BoxClone trait.
@@ -199,15 +435,149 @@ impl<FS: Clone + DavFileSystem + 'static> BoxCloneFs for FS {
     }
 }
 
+/// A boxed future resolving to an `FsResult`.
+///
+/// Used throughout [`AsyncDavFileSystem`] in place of the plain `FsResult<T>`
+/// that the synchronous trait returns directly.
+pub type FsFuture<T> = Box<Future<Item=T, Error=FsError> + Send>;
+
+/// A boxed stream of directory entries, paired with an `FsError` on failure.
+///
+/// Returned by [`AsyncDavFileSystem::read_dir`] instead of a synchronous
+/// `Iterator`, so that a backend can page results in off the network as the
+/// handler consumes them instead of buffering a whole listing up front.
+pub type FsStream<T> = Box<Stream<Item=T, Error=FsError> + Send>;
+
+/// Non-blocking seek, mirroring `std::io::Seek`.
+///
+/// `tokio_io` does not (yet) define an async counterpart to `Seek`, so
+/// `AsyncDavFile` needs its own minimal version.
+pub trait AsyncSeek {
+    /// Attempt to seek, analogous to `std::io::Seek::seek`.
+    fn poll_seek(&mut self, pos: std::io::SeekFrom) -> futures::Poll<u64, std::io::Error>;
+}
+
+/// Async counterpart of [`DavFile`].
+///
+/// Implementations are driven by `poll_read`/`poll_write`/`poll_seek` instead
+/// of blocking, so a file body can be streamed straight off (or onto) a
+/// network connection.
+pub trait AsyncDavFile: tokio_io::AsyncRead + tokio_io::AsyncWrite + AsyncSeek + Debug + Send {
+    fn metadata(&self) -> FsFuture<Box<DavMetaData>>;
+}
+
+/// Async counterpart of [`DavFileSystem`].
+///
+/// Covers `open`, `read_dir` and `metadata` only: each returns a boxed
+/// `Future<Item=T, Error=FsError>` in place of `FsResult<T>`, and `read_dir`
+/// returns a `Stream` of entries rather than a synchronous `Iterator`. The
+/// other `DavFileSystem` operations (create/remove, rename, copy, properties,
+/// xattrs, quota and the rest) have no async counterpart. This is the shape a
+/// network- or object-store-backed filesystem wants: directory listings are
+/// paginated remote calls, and file bodies are pulled (or pushed) a poll at
+/// a time instead of being buffered in full.
+///
+/// A blanket implementation below adapts any `DavFileSystem` to this trait,
+/// so existing synchronous backends keep compiling and working unchanged.
+/// A backend can only implement `AsyncDavFileSystem` itself to get real
+/// non-blocking behaviour if it does *not* also implement `DavFileSystem`
+/// (the blanket impl would otherwise conflict) -- a network- or
+/// object-store-backed filesystem that has no synchronous story at all is
+/// the intended case.
+pub trait AsyncDavFileSystem : Debug + Sync + Send {
+    /// Open a file, asynchronously.
+    fn open(&self, path: &WebPath, options: OpenOptions) -> FsFuture<Box<AsyncDavFile>>;
+
+    /// Stream the entries of a directory.
+    fn read_dir(&self, path: &WebPath) -> FsStream<Box<DavDirEntry>>;
+
+    /// Return the metadata of a file or directory.
+    fn metadata(&self, path: &WebPath) -> FsFuture<Box<DavMetaData>>;
+}
+
+// Blanket adapter: every synchronous DavFileSystem is trivially also an
+// AsyncDavFileSystem, by running the blocking call and handing back a
+// future/stream that's already resolved. This keeps current backends
+// compiling without change; it buys nothing in non-blocking-ness, it's
+// purely a source-compatibility bridge.
+impl<FS: DavFileSystem + 'static> AsyncDavFileSystem for FS {
+    fn open(&self, path: &WebPath, options: OpenOptions) -> FsFuture<Box<AsyncDavFile>> {
+        Box::new(futures::future::result(DavFileSystem::open(self, path, options).map(SyncDavFile::new)))
+    }
+
+    fn read_dir(&self, path: &WebPath) -> FsStream<Box<DavDirEntry>> {
+        match DavFileSystem::read_dir(self, path) {
+            Ok(iter) => Box::new(futures::stream::iter_ok(iter)),
+            Err(e) => Box::new(futures::stream::iter_result(vec![Err(e)])),
+        }
+    }
+
+    fn metadata(&self, path: &WebPath) -> FsFuture<Box<DavMetaData>> {
+        Box::new(futures::future::result(DavFileSystem::metadata(self, path)))
+    }
+}
+
+/// Wraps a synchronous `Box<DavFile>` so it can be handed out as a
+/// `Box<AsyncDavFile>` by the blanket adapter above.
+///
+/// `poll_read`/`poll_write`/`poll_seek` just call straight through to the
+/// blocking `Read`/`Write`/`Seek` implementation and always report "ready",
+/// which is correct but defeats the purpose of being async; real
+/// non-blocking backends should implement `AsyncDavFile` directly instead of
+/// going through this wrapper.
+#[derive(Debug)]
+struct SyncDavFile(Box<DavFile>);
+
+impl SyncDavFile {
+    fn new(inner: Box<DavFile>) -> Box<AsyncDavFile> {
+        Box::new(SyncDavFile(inner))
+    }
+}
+
+impl AsyncDavFile for SyncDavFile {
+    fn metadata(&self) -> FsFuture<Box<DavMetaData>> {
+        Box::new(futures::future::result(self.0.metadata()))
+    }
+}
+
+impl Read for SyncDavFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl tokio_io::AsyncRead for SyncDavFile {}
+
+impl Write for SyncDavFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.write(buf)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl tokio_io::AsyncWrite for SyncDavFile {
+    fn shutdown(&mut self) -> futures::Poll<(), std::io::Error> {
+        Ok(futures::Async::Ready(()))
+    }
+}
+
+impl AsyncSeek for SyncDavFile {
+    fn poll_seek(&mut self, pos: std::io::SeekFrom) -> futures::Poll<u64, std::io::Error> {
+        Ok(futures::Async::Ready(self.0.seek(pos)?))
+    }
+}
+
 /// Iterator, returned by read_dir(), that generates DavDirEntries.
 ///
 /// Often you'll end up creating an empty imp DavReadDir, plus an
 /// impl Iterator.
-pub trait DavReadDir : Iterator<Item=Box<DavDirEntry>> + Debug {
+pub trait DavReadDir : Iterator<Item=Box<DavDirEntry>> + Debug + Send {
 }
 
 /// One directory entry (or child node).
-pub trait DavDirEntry: Debug {
+pub trait DavDirEntry: Debug + Send {
     /// name of the entry.
     fn name(&self) -> Vec<u8>;
 
@@ -230,31 +600,89 @@ pub trait DavDirEntry: Debug {
 
 /// A DavFile should be readable/writeable/seekable, and be able
 /// to return its metadata.
-pub trait DavFile: Read + Write + Seek + Debug {
+pub trait DavFile: Read + Write + Seek + Debug + Send {
     fn metadata(&self) -> FsResult<Box<DavMetaData>>;
 }
 
+/// A modification time truncated to whatever resolution the backend can
+/// actually guarantee.
+///
+/// Folding a sub-second mtime down to microseconds for the ETag, and
+/// assuming every backend can supply that precision, invites spurious
+/// churn: many filesystems and most WebDAV clients only carry second
+/// resolution, so two unrelated writes landing in the same second are
+/// indistinguishable. `ambiguous` records that risk explicitly, so a
+/// consumer (like `etag()` below) can widen its comparison instead of
+/// producing an ETag that races with a concurrent write.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct TruncatedTimestamp {
+    /// seconds since the unix epoch.
+    pub secs: u64,
+    /// sub-second part, in nanoseconds, if the backend can supply it.
+    pub nanos: Option<u32>,
+    /// true if the real mtime could collide, within the same second, with
+    /// a concurrent write -- i.e. the backend's clock resolution is no
+    /// finer than one second.
+    pub ambiguous: bool,
+}
+
+impl TruncatedTimestamp {
+    /// Build a non-ambiguous `TruncatedTimestamp` from a `SystemTime`.
+    pub fn new(t: SystemTime) -> TruncatedTimestamp {
+        TruncatedTimestamp::with_ambiguity(t, false)
+    }
+
+    /// Build a `TruncatedTimestamp` from a `SystemTime`, explicitly marking
+    /// whether the second it falls in is ambiguous.
+    pub fn with_ambiguity(t: SystemTime, ambiguous: bool) -> TruncatedTimestamp {
+        match t.duration_since(UNIX_EPOCH) {
+            Ok(d) => TruncatedTimestamp{
+                secs:       d.as_secs(),
+                nanos:      Some(d.subsec_nanos()),
+                ambiguous:  ambiguous,
+            },
+            Err(_) => TruncatedTimestamp{ secs: 0, nanos: None, ambiguous: ambiguous },
+        }
+    }
+}
+
 /// Not much more than type, length, and some timestamps.
 ///
 /// The BoxCloneMd trait is a helper trait that is automatically implemented
 /// so that Box\<DavMetaData\>.clone() works.
-pub trait DavMetaData : Debug + BoxCloneMd {
+pub trait DavMetaData : Debug + Send + BoxCloneMd {
 
     fn len(&self) -> u64;
     fn modified(&self) -> FsResult<SystemTime>;
 	fn is_dir(&self) -> bool;
 
+    /// Like `modified()`, but truncated to the resolution the backend can
+    /// actually guarantee.
+    ///
+    /// Default implementation: wraps `modified()` as a non-ambiguous,
+    /// full-precision `TruncatedTimestamp`. Backends whose timestamps are
+    /// only second-resolution should override this and set `ambiguous: true`
+    /// so that `etag()` widens its comparison window accordingly.
+    fn modified_truncated(&self) -> FsResult<TruncatedTimestamp> {
+        self.modified().map(TruncatedTimestamp::new)
+    }
+
     /// Simplistic implementation of etag()
     ///
-    /// Returns a simple etag that basically is "\<length\>-\<timestamp_in_ms\>"
-    /// with the numbers in hex. Enough for most implementations.
+    /// Returns a simple etag that basically is "\<length\>-\<timestamp_in_ns\>"
+    /// with the numbers in hex, built from `modified_truncated()`. When the
+    /// timestamp is ambiguous, the sub-second part is folded out of the
+    /// etag entirely instead of claiming a precision the backend doesn't
+    /// have, so two requests landing in the same ambiguous second get the
+    /// same etag instead of racing each other.
     fn etag(&self) -> String {
-		if let Ok(t) = self.modified() {
-            if let Ok(t) = t.duration_since(UNIX_EPOCH) {
-			    // apache style etag.
-			    return format!("{:x}-{:x}", self.len(),
-				    t.as_secs() * 1000000 + t.subsec_nanos() as u64 / 1000);
-            }
+		if let Ok(t) = self.modified_truncated() {
+            let ts = match t.nanos {
+                Some(nanos) if !t.ambiguous => t.secs * 1_000_000_000 + nanos as u64,
+                _ => t.secs * 1_000_000_000,
+            };
+            // apache style etag.
+            return format!("{:x}-{:x}", self.len(), ts);
 		}
 		format!("{:x}", self.len())
 	}
@@ -311,6 +739,116 @@ impl<MD: Clone + DavMetaData + 'static> BoxCloneMd for MD {
     }
 }
 
+/// Options for rename_with_options().
+///
+/// Controls whether an existing destination is replaced, so that the
+/// WebDAV handler can honor the MOVE method's `Overwrite` header instead
+/// of a backend guessing at the right behaviour.
+#[derive(Debug,Clone,Copy)]
+pub struct RenameOptions {
+    /// if the destination exists, overwrite it instead of returning `FsError::Exists`.
+    pub overwrite: bool,
+}
+
+impl Default for RenameOptions {
+    /// `Overwrite: T` is the default per the WebDAV spec for MOVE.
+    fn default() -> RenameOptions {
+        RenameOptions{
+            overwrite: true,
+        }
+    }
+}
+
+/// Options for copy_with_options().
+///
+/// Controls whether an existing destination is replaced and whether a
+/// directory source is copied recursively, so that the WebDAV handler can
+/// honor the COPY method's `Overwrite` and `Depth` headers.
+#[derive(Debug,Clone,Copy)]
+pub struct CopyOptions {
+    /// if the destination exists, overwrite it instead of returning `FsError::Exists`.
+    pub overwrite: bool,
+    /// if `from` is a directory, copy the whole tree instead of just the node itself.
+    pub depth_infinity: bool,
+}
+
+impl Default for CopyOptions {
+    /// `Overwrite: T` and `Depth: infinity` are the defaults per the WebDAV spec for COPY.
+    fn default() -> CopyOptions {
+        CopyOptions{
+            overwrite: true,
+            depth_infinity: true,
+        }
+    }
+}
+
+/// Policy that controls how symbolic links are treated while resolving a path.
+///
+/// Carried into `open()`/`metadata()` via `OpenOptions::symlink_policy` and
+/// [`resolve_symlinks`].
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum SymlinkPolicy {
+    /// Let the backend follow the link wherever it points, exactly as it
+    /// would for any other path (the historical, and still default,
+    /// behaviour). This does *not* confine the link to any particular
+    /// subtree -- following a link means going where it points.
+    Follow,
+    /// Refuse to traverse the link; the request fails with `FsError::Forbidden`.
+    /// This is the only policy [`resolve_symlinks`] actually enforces, since
+    /// it's the only one that needs to inspect anything before the
+    /// backend's own lookup runs.
+    DenyWithForbidden,
+    /// Pretend the link is a regular file/directory and don't follow it at all.
+    ReportAsRegular,
+}
+
+impl Default for SymlinkPolicy {
+    fn default() -> SymlinkPolicy {
+        SymlinkPolicy::Follow
+    }
+}
+
+/// Maximum number of ancestors [`resolve_symlinks`] will climb (via
+/// `WebPath::parent()`) while checking a path under `DenyWithForbidden`,
+/// as a defensive bound against a pathologically deep (or malformed,
+/// non-terminating) path.
+pub const MAX_SYMLINK_DEPTH: u32 = 32;
+
+/// Apply `policy` to `path` before a backend does its real lookup.
+///
+/// * `Follow` and `ReportAsRegular` are no-ops here: following a link is
+///   exactly what the backend's own `open()`/`metadata()` already does as
+///   a normal part of resolving a path, and "report as regular" means not
+///   looking at the link at all, so neither needs anything checked
+///   up front.
+/// * `DenyWithForbidden` checks `path` itself *and every ancestor
+///   directory* -- not just the final component -- so an intermediate
+///   symlink (e.g. `/a` -> `/etc`, for a request of `/a/passwd`) is caught
+///   instead of being silently followed by the backend before this
+///   function ever sees it. Returns `FsError::Forbidden` on the first
+///   symlink found, or `FsError::LoopDetected` if the path is too deep to
+///   be a real path (see [`MAX_SYMLINK_DEPTH`]).
+pub fn resolve_symlinks(fs: &DavFileSystem, path: &WebPath, policy: SymlinkPolicy) -> FsResult<WebPath> {
+    if policy != SymlinkPolicy::DenyWithForbidden {
+        return Ok(path.clone());
+    }
+    let mut current = path.clone();
+    for _ in 0..MAX_SYMLINK_DEPTH {
+        match fs.symlink_metadata(&current) {
+            Ok(ref meta) if meta.is_symlink() => return Err(FsError::Forbidden),
+            Ok(_) | Err(FsError::NotFound) => {},
+            Err(e) => return Err(e),
+        }
+        let parent = current.parent();
+        if parent.as_bytes() == current.as_bytes() {
+            // reached the root: no more ancestors to check.
+            return Ok(path.clone());
+        }
+        current = parent;
+    }
+    Err(FsError::LoopDetected)
+}
+
 /// OpenOptions for open().
 #[derive(Debug,Clone,Copy)]
 pub struct OpenOptions {
@@ -326,6 +864,8 @@ pub struct OpenOptions {
     pub create: bool,
     /// must create new file, fail if it already exists.
     pub create_new: bool,
+    /// how to treat symbolic links encountered while resolving the path.
+    pub symlink_policy: SymlinkPolicy,
 }
 
 impl OpenOptions {
@@ -338,6 +878,7 @@ impl OpenOptions {
             truncate: false,
             create: false,
             create_new: false,
+            symlink_policy: SymlinkPolicy::Follow,
         }
     }
 
@@ -349,6 +890,7 @@ impl OpenOptions {
             truncate: false,
             create: false,
             create_new: false,
+            symlink_policy: SymlinkPolicy::Follow,
         }
     }
 
@@ -360,6 +902,7 @@ impl OpenOptions {
             truncate: false,
             create: false,
             create_new: false,
+            symlink_policy: SymlinkPolicy::Follow,
         }
     }
 }